@@ -0,0 +1,35 @@
+/// Tweaks the behaviour of the line counting performed by
+/// [`LanguageType::parse`](crate::language::LanguageType::parse) and friends.
+///
+/// Every field has a default (`None`, `false` or empty) so a `Config` can be
+/// built up piece by piece (e.g. merging a config file with command line
+/// flags) without having to know about every option up front.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    /// Whether to treat doc strings (`"""..."""` in Python, `///` in Rust,
+    /// etc.) as comments rather than code. `None` defers to the per-language
+    /// default.
+    pub treat_doc_strings_as_comments: Option<bool>,
+    /// Whether to recognise `tokei:` directive comments (`tokei:language=`,
+    /// `tokei:ignore-start`/`-end`, `tokei:count-as=`) embedded in source
+    /// files. Off by default so existing counts are unchanged.
+    pub allow_directives: bool,
+    /// Filename globs (at most one `*` wildcard each, e.g. `*.pb.go`,
+    /// `*_generated.rs`) that mark a file as machine-generated, in addition
+    /// to the conventional `@generated` comment marker which is always
+    /// recognised.
+    pub generated_file_globs: Vec<String>,
+    /// Whether to look for lines that mix code and comment content on the
+    /// same physical line (e.g. `foo(); // note`) and report them via
+    /// [`LanguageSummary::mixed_line`](crate::language::LanguageSummary::mixed_line).
+    /// Off by default, since the extra per-line scan isn't free.
+    pub detailed_mixed_lines: bool,
+}
+
+impl Config {
+    /// Creates a `Config` with every field set to its default value: `None`
+    /// (i.e. "defer to the per-language default"), `false` or empty.
+    pub fn new() -> Self {
+        Config::default()
+    }
+}
@@ -0,0 +1,109 @@
+use std::str::FromStr;
+
+use crate::language::LanguageType;
+
+/// A `tokei:` directive embedded in a comment, recognised when
+/// [`Config::allow_directives`](crate::config::Config::allow_directives) is
+/// set. Only ever parsed out of a line the existing comment machinery has
+/// already classified as a line or block comment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Directive {
+    /// `tokei:language=<name>` - force the file to be parsed as the named
+    /// `LanguageType` instead of whatever its extension implied.
+    Language(LanguageType),
+    /// `tokei:ignore-start` - exclude subsequent lines from all counts, until
+    /// a matching `tokei:ignore-end`.
+    IgnoreStart,
+    /// `tokei:ignore-end` - resume normal counting after an `ignore-start`.
+    IgnoreEnd,
+    /// `tokei:count-as=code` or `tokei:count-as=comment` - force subsequent
+    /// lines to be classified as the given kind, regardless of what the
+    /// syntax machinery would otherwise say.
+    CountAs(CountAs),
+}
+
+/// The classification a `tokei:count-as=` directive forces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CountAs {
+    Code,
+    Comment,
+}
+
+const MARKER: &[u8] = b"tokei:";
+
+/// Looks for a `tokei:` directive anywhere in `comment` and parses it if
+/// found. Returns `None` for an ordinary comment, or a `tokei:` prefix
+/// followed by something this version doesn't recognise.
+pub(crate) fn parse(comment: &[u8]) -> Option<Directive> {
+    let start = find_marker(comment)? + MARKER.len();
+    let rest = &comment[start..];
+
+    if let Some(name) = strip_prefix(rest, b"language=") {
+        let name = std::str::from_utf8(trim_word(name)).ok()?;
+        return name.parse::<LanguageType>().ok().map(Directive::Language);
+    }
+
+    if rest.starts_with(b"ignore-start") {
+        return Some(Directive::IgnoreStart);
+    }
+
+    if rest.starts_with(b"ignore-end") {
+        return Some(Directive::IgnoreEnd);
+    }
+
+    if let Some(value) = strip_prefix(rest, b"count-as=") {
+        return match trim_word(value) {
+            b"code" => Some(Directive::CountAs(CountAs::Code)),
+            b"comment" => Some(Directive::CountAs(CountAs::Comment)),
+            _ => None,
+        };
+    }
+
+    None
+}
+
+/// The byte offset of `MARKER` within `line`, if present.
+fn find_marker(line: &[u8]) -> Option<usize> {
+    line.windows(MARKER.len()).position(|window| window == MARKER)
+}
+
+/// Strips `prefix` from the front of `line`, if present.
+fn strip_prefix<'a>(line: &'a [u8], prefix: &[u8]) -> Option<&'a [u8]> {
+    if line.starts_with(prefix) {
+        Some(&line[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// The leading run of non-whitespace bytes in `value`, e.g. the `Python` in
+/// `Python  -- force this file to be parsed as Python`.
+fn trim_word(value: &[u8]) -> &[u8] {
+    let end = value.iter().position(|b| b.is_ascii_whitespace()).unwrap_or(value.len());
+    &value[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ignore_and_count_as_directives() {
+        assert_eq!(parse(b"// tokei:ignore-start"), Some(Directive::IgnoreStart));
+        assert_eq!(parse(b"# tokei:ignore-end"), Some(Directive::IgnoreEnd));
+        assert_eq!(
+            parse(b"// tokei:count-as=comment please"),
+            Some(Directive::CountAs(CountAs::Comment))
+        );
+    }
+
+    #[test]
+    fn parses_language_override_and_ignores_unknown_directives() {
+        assert_eq!(
+            parse(b"// tokei:language=Python"),
+            Some(Directive::Language(LanguageType::Python))
+        );
+        assert_eq!(parse(b"// tokei:frobnicate"), None);
+        assert_eq!(parse(b"// just a normal comment"), None);
+    }
+}
@@ -0,0 +1,30 @@
+use crate::language::LanguageType;
+
+/// A region of one language's source that is embedded inside another, e.g.
+/// `<script>...</script>` inside HTML.
+#[derive(Clone, Copy, Debug)]
+pub struct EmbeddedRegion {
+    /// The delimiter that opens the region.
+    pub start: &'static str,
+    /// The delimiter that closes the region.
+    pub end: &'static str,
+    /// The language the enclosed lines should be counted as.
+    pub language: LanguageType,
+}
+
+/// Returns the embedded-region delimiters recognised for `language`, or an
+/// empty slice for languages with no embedding rules.
+pub(crate) fn embedded_regions_for(language: LanguageType) -> &'static [EmbeddedRegion] {
+    use LanguageType::*;
+
+    match language {
+        Html => &[
+            EmbeddedRegion { start: "<script>", end: "</script>", language: JavaScript },
+            EmbeddedRegion { start: "<style>", end: "</style>", language: Css },
+        ],
+        Erb => &[
+            EmbeddedRegion { start: "<%", end: "%>", language: Ruby },
+        ],
+        _ => &[],
+    }
+}
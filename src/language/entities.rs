@@ -0,0 +1,190 @@
+use crate::{language::LanguageType, utils::ext::SliceExt};
+
+/// The coarse category of a named entity recognised by
+/// [`LanguageSummary::code_entity`](crate::language::LanguageSummary::code_entity).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    /// A function, method or procedure definition.
+    Function,
+    /// A type definition: struct, class, enum, interface, union, etc.
+    Type,
+}
+
+/// A lightweight byte-level pattern matched against a line that the existing
+/// comment/string machinery has already classified as code. Deliberately not
+/// a full regex: these only ever need to check a handful of leading keywords.
+struct EntityPattern {
+    kind: EntityKind,
+    matches: fn(&[u8]) -> bool,
+}
+
+/// Returns the entity patterns to check code lines of `language` against, or
+/// an empty slice for languages with no recognised signature shape yet.
+pub(crate) fn patterns_for(language: LanguageType) -> &'static [EntityPattern] {
+    use LanguageType::*;
+
+    match language {
+        Rust => &[
+            EntityPattern { kind: EntityKind::Function, matches: rust_function },
+            EntityPattern { kind: EntityKind::Type, matches: rust_type },
+        ],
+        Python => &[
+            EntityPattern { kind: EntityKind::Function, matches: python_function },
+            EntityPattern { kind: EntityKind::Type, matches: python_type },
+        ],
+        Go => &[
+            EntityPattern { kind: EntityKind::Function, matches: go_function },
+            EntityPattern { kind: EntityKind::Type, matches: go_type },
+        ],
+        Java => &[
+            EntityPattern { kind: EntityKind::Function, matches: c_like_function },
+            EntityPattern { kind: EntityKind::Type, matches: java_type },
+        ],
+        C | Cpp => &[
+            EntityPattern { kind: EntityKind::Function, matches: c_like_function },
+            EntityPattern { kind: EntityKind::Type, matches: c_type },
+        ],
+        _ => &[],
+    }
+}
+
+/// Scans `line` (already known to be code, not comment or string) against
+/// `language`'s patterns and returns the kind of the first one it matches.
+pub(crate) fn classify(language: LanguageType, line: &[u8]) -> Option<EntityKind> {
+    patterns_for(language)
+        .iter()
+        .find(|pattern| (pattern.matches)(line))
+        .map(|pattern| pattern.kind)
+}
+
+/// Strips any of `words` (each followed by whitespace) from the front of
+/// `line`, one or more times, e.g. `pub async fn` -> `fn` once `pub` and
+/// `async` are both stripped.
+fn strip_leading_words<'a>(mut line: &'a [u8], words: &[&[u8]]) -> &'a [u8] {
+    loop {
+        let stripped = words.iter().find_map(|&word| {
+            if starts_with_word(line, word) {
+                Some(trim_start(&line[word.len()..]))
+            } else {
+                None
+            }
+        });
+
+        match stripped {
+            Some(rest) => line = rest,
+            None => return line,
+        }
+    }
+}
+
+/// Trims leading ASCII whitespace from `line`.
+fn trim_start(line: &[u8]) -> &[u8] {
+    let start = line.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(line.len());
+    &line[start..]
+}
+
+/// Returns `true` if `line` starts with `word` as a whole word, i.e. not
+/// immediately followed by another identifier character.
+fn starts_with_word(line: &[u8], word: &[u8]) -> bool {
+    line.starts_with(word)
+        && line
+            .get(word.len())
+            .map_or(true, |b| !b.is_ascii_alphanumeric() && *b != b'_')
+}
+
+fn rust_function(line: &[u8]) -> bool {
+    let line = strip_leading_words(line, &[b"pub", b"const", b"async", b"unsafe", b"extern"]);
+    starts_with_word(line, b"fn")
+}
+
+fn rust_type(line: &[u8]) -> bool {
+    let line = strip_leading_words(line, &[b"pub"]);
+    [b"struct".as_ref(), b"enum", b"trait", b"union"]
+        .iter()
+        .any(|keyword| starts_with_word(line, keyword))
+}
+
+fn python_function(line: &[u8]) -> bool {
+    let line = strip_leading_words(line, &[b"async"]);
+    starts_with_word(line, b"def")
+}
+
+fn python_type(line: &[u8]) -> bool {
+    starts_with_word(line, b"class")
+}
+
+fn go_function(line: &[u8]) -> bool {
+    starts_with_word(line, b"func")
+}
+
+fn go_type(line: &[u8]) -> bool {
+    starts_with_word(line, b"type")
+}
+
+fn java_type(line: &[u8]) -> bool {
+    let line = strip_leading_words(
+        line,
+        &[b"public", b"private", b"protected", b"abstract", b"final", b"static"],
+    );
+    [b"class".as_ref(), b"interface", b"enum", b"record"]
+        .iter()
+        .any(|keyword| starts_with_word(line, keyword))
+}
+
+fn c_type(line: &[u8]) -> bool {
+    [b"struct".as_ref(), b"enum", b"union"]
+        .iter()
+        .any(|keyword| starts_with_word(line, keyword))
+}
+
+/// Heuristic shared by C, C++ and Java: a function/method definition looks
+/// like a parenthesised parameter list that isn't a control-flow statement,
+/// a prototype (ending in `;`), a preprocessor directive, or (for Java) an
+/// annotation such as `@RequestMapping("/users")`.
+const CONTROL_KEYWORDS: &[&[u8]] = &[
+    b"if", b"for", b"while", b"switch", b"catch", b"return", b"else",
+];
+
+fn c_like_function(line: &[u8]) -> bool {
+    if line.starts_with(b"#") || line.starts_with(b"@") || line.ends_with(b";") || !line.contains_slice(b"(") {
+        return false;
+    }
+
+    !CONTROL_KEYWORDS
+        .iter()
+        .any(|keyword| starts_with_word(line, keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_rust_functions_and_types() {
+        assert_eq!(classify(LanguageType::Rust, b"pub async fn parse() {"), Some(EntityKind::Function));
+        assert_eq!(classify(LanguageType::Rust, b"pub struct Stats {"), Some(EntityKind::Type));
+        assert_eq!(classify(LanguageType::Rust, b"let functional = 1;"), None);
+    }
+
+    #[test]
+    fn classifies_python_and_go() {
+        assert_eq!(classify(LanguageType::Python, b"def parse(self):"), Some(EntityKind::Function));
+        assert_eq!(classify(LanguageType::Python, b"class Stats:"), Some(EntityKind::Type));
+        assert_eq!(classify(LanguageType::Go, b"func Parse() error {"), Some(EntityKind::Function));
+        assert_eq!(classify(LanguageType::Go, b"type Stats struct {"), Some(EntityKind::Type));
+    }
+
+    #[test]
+    fn c_like_function_ignores_control_flow_and_prototypes() {
+        assert!(c_like_function(b"int parse(char *line) {"));
+        assert!(!c_like_function(b"if (parse(line)) {"));
+        assert!(!c_like_function(b"int parse(char *line);"));
+        assert!(!c_like_function(b"#define PARSE(line) parse(line)"));
+    }
+
+    #[test]
+    fn c_like_function_ignores_java_annotations() {
+        assert!(!c_like_function(b"@RequestMapping(\"/users\")"));
+        assert!(!c_like_function(b"@Column(name = \"id\")"));
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use grep_searcher::LineIter;
+
+use crate::{
+    config::Config,
+    language::{syntax::SyntaxCounter, tokens::classify_line, LanguageType, TokenKind},
+    utils::ext::SliceExt,
+};
+
+/// How many leading physical lines are scanned for an `@generated` marker.
+const SCAN_LINES: usize = 5;
+const MARKER: &[u8] = b"@generated";
+
+/// Returns `true` if `path` matches one of `config`'s configured
+/// generated-file globs, or if an `@generated` marker appears inside a
+/// recognised comment within the first [`SCAN_LINES`] lines of `text`.
+pub(crate) fn is_generated(language: LanguageType, path: &Path, text: &[u8], config: &Config) -> bool {
+    matches_any_glob(path, &config.generated_file_globs) || has_generated_marker(language, text)
+}
+
+fn matches_any_glob(path: &Path, globs: &[String]) -> bool {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+
+    globs.iter().any(|glob| glob_match(glob, name))
+}
+
+/// Matches `name` against `pattern`, a filename glob with at most one `*`
+/// wildcard (e.g. `*.pb.go`, `*_generated.rs`) - enough for the conventional
+/// generated-file naming schemes without pulling in a full glob engine.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            name.len() >= prefix.len() + suffix.len()
+                && name.starts_with(prefix)
+                && name.ends_with(suffix)
+        }
+        None => name == pattern,
+    }
+}
+
+/// Scans the first few lines of `text`, using `language`'s own comment
+/// syntax, for a comment containing `@generated` - so a string literal or
+/// piece of code mentioning it is never mistaken for the marker.
+fn has_generated_marker(language: LanguageType, text: &[u8]) -> bool {
+    let mut syntax = SyntaxCounter::new(language);
+
+    for raw_line in LineIter::new(b'\n', text).take(SCAN_LINES) {
+        let (kind, _) = classify_line(&mut syntax, raw_line);
+
+        if let TokenKind::LineComment | TokenKind::BlockComment = kind {
+            if raw_line.contains_slice(MARKER) {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_prefix_and_suffix_wildcards() {
+        assert!(glob_match("*.pb.go", "user.pb.go"));
+        assert!(glob_match("*_generated.rs", "schema_generated.rs"));
+        assert!(!glob_match("*.pb.go", "user.go"));
+    }
+
+    #[test]
+    fn detects_marker_inside_a_comment_but_not_a_string() {
+        assert!(has_generated_marker(LanguageType::Rust, b"// @generated by some tool\nfn x() {}\n"));
+        assert!(!has_generated_marker(LanguageType::Rust, b"let s = \"@generated\";\n"));
+    }
+}
@@ -12,7 +12,13 @@ use encoding_rs_io::DecodeReaderBytesBuilder;
 
 use crate::{
     config::Config,
-    language::syntax::SyntaxCounter,
+    language::{
+        directives::{self, CountAs, Directive},
+        entities, entities::EntityKind,
+        generated,
+        syntax::SyntaxCounter,
+        tokens::{classify_line, TokenKind, Tokens},
+    },
     stats::Stats,
     utils::{ext::SliceExt, fs as fsutils},
 };
@@ -33,6 +39,18 @@ pub trait LanguageSummary {
     fn comment_line(&mut self, line:&[u8]);
     /// handle a blank line
     fn blank_line(&mut self, line:&[u8]);
+    /// handle a named entity (function, type, ...) found on a code line
+    fn code_entity(&mut self, kind: EntityKind, line:&[u8]);
+    /// handle a line excluded from every other count by a `tokei:` directive
+    fn ignored_line(&mut self, line:&[u8]);
+    /// flag this file as machine-generated
+    fn mark_generated(&mut self);
+    /// record a line that mixed code and comment content (e.g. `foo(); // note`)
+    fn mixed_line(&mut self, line: &[u8], code_bytes: usize, comment_bytes: usize);
+    /// the summary that lines of an embedded `language` region (e.g. the JS
+    /// inside an HTML `<script>` block) should be reported to, creating it
+    /// on first use
+    fn embedded(&mut self, language: LanguageType) -> &mut Self;
     /// processing after aggregation - e.g. for calculating totals
     fn postprocess(&mut self);
 }
@@ -58,8 +76,37 @@ impl LanguageSummary for Stats {
         self.blanks += 1;
         trace!("Blank No.{}", self.blanks);
     }
+    fn code_entity(&mut self, kind: EntityKind, _line:&[u8]) {
+        match kind {
+            EntityKind::Function => self.functions += 1,
+            EntityKind::Type => self.types += 1,
+        }
+        self.declarations += 1;
+        trace!("Entity No.{}", self.declarations);
+    }
+    fn ignored_line(&mut self, _line:&[u8]) {
+        self.ignored_lines += 1;
+        trace!("Ignored No.{}", self.ignored_lines);
+    }
+    fn mark_generated(&mut self) {
+        self.generated = true;
+        trace!("Generated file");
+    }
+    fn mixed_line(&mut self, _line: &[u8], _code_bytes: usize, _comment_bytes: usize) {
+        self.mixed += 1;
+        trace!("Mixed No.{}", self.mixed);
+    }
+    fn embedded(&mut self, language: LanguageType) -> &mut Self {
+        self.children
+            .entry(language)
+            .or_insert_with(|| Stats::new(self.name.clone()))
+    }
     fn postprocess(&mut self) {
         self.lines = self.blanks + self.code + self.comments;
+
+        for child in self.children.values_mut() {
+            child.postprocess();
+        }
     }
 }
 
@@ -102,153 +149,145 @@ impl LanguageType {
                           config: &Config)
         -> T
     {
-        let lines = LineIter::new(b'\n', text.as_ref());
-        let mut summary = T::new(path);
-
         if self.is_blank() {
-            summary.unprocessed_lines(lines);
-            summary
-        } else {
-            self.parse_lines(config, lines, summary)
+            let mut summary = T::new(path);
+            summary.unprocessed_lines(LineIter::new(b'\n', text.as_ref()));
+            return summary;
         }
-    }
 
-    /// Attempts to parse the line as simply as possible if there are no multi
-    /// line comments or quotes. Returns `bool` indicating whether it was
-    /// successful or not.
-    #[inline]
-    fn parse_basic<T: LanguageSummary>(self, syntax: &SyntaxCounter, raw_line: &[u8], line: &[u8], stats: &mut T)
-        -> bool
-    {
-        if syntax.quote.is_some() ||
-           !syntax.stack.is_empty() ||
-           syntax.important_syntax().any(|s| line.contains_slice(s.as_bytes()))
-        {
-            return false;
-        }
+        let language = self.resolve_language(text.as_ref(), config);
+        let is_generated = generated::is_generated(language, &path, text.as_ref(), config);
 
-        if syntax.line_comments.iter()
-                               .any(|s| line.starts_with(s.as_bytes()))
-        {
-            stats.comment_line(raw_line);
-        } else {
-            stats.code_line(raw_line);
+        let mut summary = T::new(path);
+        if is_generated {
+            summary.mark_generated();
         }
 
-        trace!("{}", String::from_utf8_lossy(line));
-        trace!("^ Skippable.");
+        let mut ignoring = false;
+        let mut count_as: Option<CountAs> = None;
+        // `ignore-start` and `count-as=` take effect from the line after the
+        // directive, so the directive's own comment line is counted normally.
+        let mut pending: Option<Directive> = None;
 
-        true
-    }
+        for token in language.tokens(text.as_ref(), config) {
+            let stats = language.target(token.language, &mut summary);
 
-    #[inline]
-    fn parse_lines<'a, T: LanguageSummary>(self,
-                       config: &Config,
-                       lines: impl IntoIterator<Item=&'a [u8]>,
-                       mut stats: T)
-        -> T
-    {
-        let mut syntax = SyntaxCounter::new(self);
+            match pending.take() {
+                Some(Directive::IgnoreStart) => ignoring = true,
+                Some(Directive::CountAs(kind)) => count_as = Some(kind),
+                _ => {}
+            }
 
-        for raw_line in lines {
+            if config.allow_directives {
+                if let TokenKind::LineComment | TokenKind::BlockComment = token.kind {
+                    match directives::parse(token.bytes) {
+                        // only honoured within the first lines, by `resolve_language`
+                        Some(Directive::Language(_)) => {}
+                        Some(Directive::IgnoreEnd) => ignoring = false,
+                        directive => pending = directive,
+                    }
+                }
+            }
 
-            if raw_line.trim().is_empty() {
-                stats.blank_line(raw_line);
+            if ignoring {
+                stats.ignored_line(token.bytes);
                 continue;
             }
 
-            // FORTRAN has a rule where it only counts as a comment if it's the
-            // first character in the column, so removing starting whitespace
-            // could cause a miscount.
-            let line = if syntax.is_fortran { raw_line } else { raw_line.trim() };
-            let had_multi_line = !syntax.stack.is_empty();
-            let mut ended_with_comments = false;
-            let mut skip = 0;
-            macro_rules! skip {
-                ($skip:expr) => {{
-                    skip = $skip - 1;
-                }}
+            if let Some((code_bytes, comment_bytes)) = token.mixed {
+                stats.mixed_line(token.bytes, code_bytes, comment_bytes);
             }
 
-            if self.parse_basic(&syntax, raw_line, line, &mut stats) {
-                continue;
-            }
+            let kind = match (token.kind, count_as) {
+                (TokenKind::Blank, _) | (_, None) => token.kind,
+                (_, Some(CountAs::Code)) => TokenKind::Code,
+                (_, Some(CountAs::Comment)) => TokenKind::LineComment,
+            };
 
-            'window: for i in 0..line.len() {
-                if skip != 0 {
-                    skip -= 1;
-                    continue;
+            match kind {
+                TokenKind::Blank => stats.blank_line(token.bytes),
+                TokenKind::LineComment | TokenKind::BlockComment => stats.comment_line(token.bytes),
+                TokenKind::DocQuote if config.treat_doc_strings_as_comments == Some(true) => {
+                    stats.comment_line(token.bytes);
+                }
+                TokenKind::DocQuote | TokenKind::Quote | TokenKind::Code => {
+                    stats.code_line(token.bytes);
+                    if token.kind == TokenKind::Code {
+                        let line = if token.language.is_fortran() {
+                            token.bytes
+                        } else {
+                            token.bytes.trim()
+                        };
+                        Self::report_entity(token.language, line, stats);
+                    }
                 }
+            }
+        }
 
-                ended_with_comments = false;
-                let window = &line[i..];
+        summary.postprocess();
+        summary
+    }
 
-                let is_end_of_quote_or_multi_line =
-                    syntax.parse_end_of_quote(window)
-                    .or_else(|| syntax.parse_end_of_multi_line(window));
+    /// Scans the first few lines of `text` for a `tokei:language=` directive
+    /// and returns the language it names, or `self` if directives are
+    /// disabled or none is found. Kept separate from the main counting pass
+    /// since it has to run (and pick a `SyntaxCounter`) before we know which
+    /// language's comment syntax the rest of the file should use.
+    fn resolve_language(self, text: &[u8], config: &Config) -> LanguageType {
+        const DIRECTIVE_SCAN_LINES: usize = 5;
 
-                if let Some(skip_amount) = is_end_of_quote_or_multi_line {
-                    ended_with_comments = true;
-                    skip!(skip_amount);
-                    continue;
-                } else if syntax.quote.is_some() {
-                    continue;
-                }
+        if !config.allow_directives {
+            return self;
+        }
 
-                let is_quote_or_multi_line = syntax.parse_quote(window)
-                    .or_else(|| syntax.parse_multi_line_comment(window));
+        let mut syntax = SyntaxCounter::new(self);
 
-                if let Some(skip_amount) = is_quote_or_multi_line {
-                    skip!(skip_amount);
-                    continue;
-                }
+        for raw_line in LineIter::new(b'\n', text).take(DIRECTIVE_SCAN_LINES) {
+            let (kind, _) = classify_line(&mut syntax, raw_line);
 
-                if syntax.parse_line_comment(window) {
-                    ended_with_comments = true;
-                    break 'window;
+            if let TokenKind::LineComment | TokenKind::BlockComment = kind {
+                if let Some(Directive::Language(language)) = directives::parse(raw_line) {
+                    return language;
                 }
-
             }
+        }
 
-            trace!("{}", String::from_utf8_lossy(line));
-
-            let is_comments =
-                (
-                    (!syntax.stack.is_empty() || ended_with_comments) &&
-                     had_multi_line
-                ) ||
-                (
-                    // If we're currently in a comment or we just ended
-                    // with one.
-                    syntax.start_of_comments().any(|comment| {
-                        line.starts_with(comment.as_bytes())
-                    }) &&
-                    syntax.quote.is_none()
-                ) ||
-                (
-                    (
-                        // If we're currently in a doc string or we just ended
-                        // with one.
-                        syntax.quote.is_some() ||
-                        syntax.doc_quotes.iter().any(|(s, _)| line.starts_with(s.as_bytes()))
-                    ) &&
-                    // `Some(true)` is import in order to respect the current
-                    // configuration.
-                    config.treat_doc_strings_as_comments == Some(true) &&
-                    syntax.quote_is_doc_quote
-                );
-
-
-            if is_comments {
-                stats.comment_line(raw_line);
-                trace!("Was the Comment stack empty?: {}", !had_multi_line);
-            } else {
-                stats.code_line(raw_line);
-            }
+        self
+    }
+
+    /// Returns a streaming classifier over the physical lines of `text`,
+    /// without driving a [`LanguageSummary`]. Lets downstream tools (editors,
+    /// diff-coverage, doc generators) ask "what is this byte range?" without
+    /// re-running all of tokei's counting logic.
+    pub fn tokens<'a>(self, text: &'a [u8], config: &'a Config) -> Tokens<'a> {
+        Tokens {
+            config,
+            syntax: SyntaxCounter::new(self),
+            lines: LineIter::new(b'\n', text),
+            offset: 0,
         }
+    }
 
-        stats.postprocess();
-        stats
+    /// Checks `line` (already classified as code, never a comment or
+    /// string) against `language`'s entity signatures and reports the first
+    /// match to `stats`.
+    #[inline]
+    fn report_entity<T: LanguageSummary>(language: LanguageType, line: &[u8], stats: &mut T) {
+        if let Some(kind) = entities::classify(language, line) {
+            stats.code_entity(kind, line);
+        }
+    }
+
+    /// The summary a `token`'s lines should be reported to: `stats` itself
+    /// if `token_language` is this (the host) language, or its
+    /// embedded-language entry otherwise.
+    #[inline]
+    fn target<T: LanguageSummary>(self, token_language: LanguageType, stats: &mut T) -> &mut T {
+        if token_language == self {
+            stats
+        } else {
+            stats.embedded(token_language)
+        }
     }
 }
 
@@ -260,4 +299,28 @@ mod tests {
     fn rust_allows_nested() {
         assert!(LanguageType::Rust.allows_nested());
     }
+
+    #[test]
+    fn counts_entities_on_indented_lines() {
+        let config = Config::new();
+        let text = b"impl Foo {\n    pub fn bar() {}\n}\n";
+
+        let stats: Stats = LanguageType::Rust.parse_from_slice(PathBuf::new(), text, &config);
+
+        assert_eq!(stats.functions, 1);
+    }
+
+    #[test]
+    fn embedded_region_tags_belong_to_the_host() {
+        let config = Config::new();
+        let text = b"<div>\n<script>\nconsole.log(1);\n</script>\n</div>\n";
+
+        let stats: Stats = LanguageType::Html.parse_from_slice(PathBuf::new(), text, &config);
+        let js = &stats.children[&LanguageType::JavaScript];
+
+        // `<script>` and `</script>` are HTML markup; only the line between
+        // them is JavaScript.
+        assert_eq!(stats.code, 4);
+        assert_eq!(js.code, 1);
+    }
 }
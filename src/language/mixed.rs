@@ -0,0 +1,121 @@
+use crate::{
+    language::{syntax::SyntaxCounter, TokenKind},
+    utils::ext::SliceExt,
+};
+
+/// Looks for a line mixing code and comment content, e.g. `foo(); // note`,
+/// by scanning `line` for the first point where one of `scope`'s comment
+/// delimiters starts outside of a quoted string, with non-whitespace code
+/// bytes before it. Returns the `(code_bytes, comment_bytes)` split of the
+/// trimmed line either side of that point, or `None` if the line is purely
+/// one or the other.
+///
+/// `quote_open_at_line_start` is the closing delimiter `scope` was already
+/// inside of before this line started (i.e. a quote or doc quote opened on
+/// an earlier line and still unterminated), or `None` if the line starts
+/// outside any quote. Without this, a continuation line of a multi-line
+/// string would be scanned as if it were fresh code, and any `//`-like
+/// substring in its content would be misreported as a trailing comment.
+///
+/// Returns `None` for any line the main classifier didn't decide is
+/// code-ish (`Code`, `Quote` or `DocQuote`); doesn't attempt to handle a
+/// comment that itself ends and further code resumes afterwards on the
+/// same line (e.g. `/* a */ b();`) - rare enough in practice not to be
+/// worth the extra bookkeeping here.
+pub(crate) fn classify(
+    kind: TokenKind,
+    scope: &SyntaxCounter,
+    line: &[u8],
+    quote_open_at_line_start: Option<&'static str>,
+) -> Option<(usize, usize)> {
+    if !matches!(kind, TokenKind::Code | TokenKind::Quote | TokenKind::DocQuote) {
+        return None;
+    }
+
+    let line = line.trim();
+    let mut in_quote = quote_open_at_line_start;
+    let mut i = 0;
+
+    while i < line.len() {
+        let window = &line[i..];
+
+        if let Some(end) = in_quote {
+            if window.starts_with(end.as_bytes()) {
+                i += end.len();
+                in_quote = None;
+            } else {
+                i += 1;
+            }
+            continue;
+        }
+
+        let starts_comment = scope.multi_line_comments.iter().any(|&(start, _)| window.starts_with(start.as_bytes()))
+            || scope.line_comments.iter().any(|start| window.starts_with(start.as_bytes()));
+
+        if starts_comment {
+            let code_bytes = i;
+            let comment_bytes = line.len() - i;
+
+            return if code_bytes > 0 && comment_bytes > 0 {
+                Some((code_bytes, comment_bytes))
+            } else {
+                None
+            };
+        }
+
+        if let Some(&(start, end)) = scope.doc_quotes.iter().chain(scope.quotes.iter())
+            .find(|&&(start, _)| window.starts_with(start.as_bytes()))
+        {
+            in_quote = Some(end);
+            i += start.len();
+            continue;
+        }
+
+        i += 1;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::language::LanguageType;
+
+    #[test]
+    fn finds_trailing_line_comment_after_code() {
+        let syntax = SyntaxCounter::new(LanguageType::Rust);
+
+        assert_eq!(
+            classify(TokenKind::Code, syntax.active(), b"foo(); // note", None),
+            Some((7, 7))
+        );
+    }
+
+    #[test]
+    fn ignores_comment_markers_inside_a_string() {
+        let syntax = SyntaxCounter::new(LanguageType::Rust);
+
+        assert_eq!(
+            classify(TokenKind::Code, syntax.active(), b"let s = \"// not a comment\";", None),
+            None
+        );
+    }
+
+    #[test]
+    fn pure_comment_line_is_not_mixed() {
+        let syntax = SyntaxCounter::new(LanguageType::Rust);
+
+        assert_eq!(classify(TokenKind::LineComment, syntax.active(), b"// just a comment", None), None);
+    }
+
+    #[test]
+    fn ignores_comment_markers_on_a_string_continuation_line() {
+        let syntax = SyntaxCounter::new(LanguageType::Rust);
+
+        assert_eq!(
+            classify(TokenKind::Quote, syntax.active(), b"abc // fake comment", Some("\"")),
+            None
+        );
+    }
+}
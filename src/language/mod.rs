@@ -0,0 +1,14 @@
+pub mod directives;
+pub mod embedded;
+pub mod entities;
+pub mod generated;
+mod language_type;
+mod mixed;
+pub mod syntax;
+pub mod tokens;
+
+pub use self::{
+    entities::EntityKind,
+    language_type::{LanguageSummary, LanguageType},
+    tokens::{Token, TokenKind, Tokens},
+};
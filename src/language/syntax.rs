@@ -0,0 +1,277 @@
+use crate::language::{embedded::embedded_regions_for, LanguageType};
+
+/// Tracks the state needed to classify the lines of a single file (or
+/// embedded region of a file) as code, comment or blank: which multi-line
+/// comments are currently open, whether we're inside a quoted string, and
+/// the language-specific tables of delimiters to look for.
+///
+/// A `SyntaxCounter` also owns a stack of `children`: nested counters for
+/// languages embedded inside this one (e.g. JavaScript inside HTML). Only
+/// the innermost counter - returned by [`active`](SyntaxCounter::active) -
+/// is ever used to classify a line; the stack just remembers how to get back
+/// to the host language once the embedded region closes.
+pub struct SyntaxCounter {
+    pub language: LanguageType,
+    /// FORTRAN only counts a line as a comment if the comment marker is the
+    /// first character in the column, so leading whitespace must be kept.
+    pub is_fortran: bool,
+    /// The closing delimiter of the quote we're currently inside, if any.
+    pub quote: Option<&'static str>,
+    /// Whether the currently open quote is a doc quote (e.g. Python's
+    /// `"""..."""`) rather than an ordinary string literal.
+    pub quote_is_doc_quote: bool,
+    /// Stack of closing delimiters for the multi-line comments we're
+    /// currently nested inside, innermost last.
+    pub stack: Vec<&'static str>,
+    pub line_comments: &'static [&'static str],
+    pub multi_line_comments: &'static [(&'static str, &'static str)],
+    pub quotes: &'static [(&'static str, &'static str)],
+    pub doc_quotes: &'static [(&'static str, &'static str)],
+    /// The delimiter that closes this counter's own embedded region, if this
+    /// counter was itself pushed as a child rather than created for the host
+    /// file.
+    embedded_end: Option<&'static str>,
+    /// Counters for languages embedded inside this one, outermost first.
+    children: Vec<SyntaxCounter>,
+}
+
+impl SyntaxCounter {
+    /// Creates a new, blank `SyntaxCounter` for `language`.
+    pub fn new(language: LanguageType) -> Self {
+        SyntaxCounter {
+            language,
+            is_fortran: language.is_fortran(),
+            quote: None,
+            quote_is_doc_quote: false,
+            stack: Vec::new(),
+            line_comments: language.line_comments(),
+            multi_line_comments: language.multi_line_comments(),
+            quotes: language.quotes(),
+            doc_quotes: language.doc_quotes(),
+            embedded_end: None,
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a counter for an embedded region of `language`, closed by
+    /// `end`.
+    fn new_child(language: LanguageType, end: &'static str) -> Self {
+        SyntaxCounter {
+            embedded_end: Some(end),
+            ..Self::new(language)
+        }
+    }
+
+    /// The counter currently responsible for classifying lines: the
+    /// innermost embedded child, or `self` if no embedded region is open.
+    pub fn active(&self) -> &SyntaxCounter {
+        let mut counter = self;
+        while let Some(child) = counter.children.last() {
+            counter = child;
+        }
+        counter
+    }
+
+    /// Mutable version of [`active`](SyntaxCounter::active).
+    pub fn active_mut(&mut self) -> &mut SyntaxCounter {
+        let mut counter = self;
+        while counter.children.last().is_some() {
+            counter = counter.children.last_mut().unwrap();
+        }
+        counter
+    }
+
+    /// The languages of each embedded region currently open, outermost
+    /// first - i.e. how to walk from the host file's summary down to the
+    /// summary the active counter's lines should be reported to.
+    pub fn embedded_languages(&self) -> impl Iterator<Item = LanguageType> + '_ {
+        let mut counter = self;
+        std::iter::from_fn(move || {
+            let child = counter.children.last()?;
+            counter = child;
+            Some(child.language)
+        })
+    }
+
+    /// The counter whose `children` the currently active counter lives in -
+    /// `self` if no region is open or only one level deep, otherwise the
+    /// parent of the innermost counter.
+    fn parent_of_active_mut(&mut self) -> &mut SyntaxCounter {
+        if self.children.last().map_or(true, |child| child.children.is_empty()) {
+            self
+        } else {
+            self.children.last_mut().unwrap().parent_of_active_mut()
+        }
+    }
+
+    /// If the active counter is not mid-quote/mid-comment and `window`
+    /// starts one of its language's embedded-region delimiters, pushes a new
+    /// child counter for the embedded language.
+    pub fn parse_embedded_open(&mut self, window: &[u8]) -> Option<usize> {
+        let scope = self.active_mut();
+
+        if scope.quote.is_some() || !scope.stack.is_empty() {
+            return None;
+        }
+
+        for &region in embedded_regions_for(scope.language) {
+            if window.starts_with(region.start.as_bytes()) {
+                scope.children.push(SyntaxCounter::new_child(region.language, region.end));
+                return Some(region.start.len());
+            }
+        }
+
+        None
+    }
+
+    /// If the active counter is not mid-quote/mid-comment and `window`
+    /// starts its own closing delimiter, pops it back off the stack.
+    pub fn parse_embedded_close(&mut self, window: &[u8]) -> Option<usize> {
+        let parent = self.parent_of_active_mut();
+        let active = parent.children.last()?;
+
+        if active.quote.is_some() || !active.stack.is_empty() {
+            return None;
+        }
+
+        let end = active.embedded_end?;
+
+        if window.starts_with(end.as_bytes()) {
+            parent.children.pop();
+            Some(end.len())
+        } else {
+            None
+        }
+    }
+
+    /// All the delimiters worth scanning a line for before bothering with
+    /// the byte-by-byte window loop: the active counter's own quotes and
+    /// comments, plus any embedded-region delimiter that could open or close
+    /// on this line. Used by `classify_basic` as a fast rejection test.
+    pub fn important_syntax(&self) -> impl Iterator<Item = &'static str> + '_ {
+        let scope = self.active();
+
+        scope
+            .quotes
+            .iter()
+            .map(|&(start, _)| start)
+            .chain(scope.doc_quotes.iter().map(|&(start, _)| start))
+            .chain(scope.multi_line_comments.iter().map(|&(start, _)| start))
+            .chain(scope.line_comments.iter().copied())
+            .chain(embedded_regions_for(scope.language).iter().map(|region| region.start))
+            .chain(scope.embedded_end)
+    }
+
+    /// The delimiters that mark the start of a comment, used to decide
+    /// whether a line beginning mid-construct should still count as a
+    /// comment line.
+    pub fn start_of_comments(&self) -> impl Iterator<Item = &'static str> + '_ {
+        let scope = self.active();
+
+        scope
+            .line_comments
+            .iter()
+            .copied()
+            .chain(scope.multi_line_comments.iter().map(|&(start, _)| start))
+    }
+
+    /// If `window` starts with the closing delimiter of the currently open
+    /// quote, consumes it and clears `self.quote`.
+    pub fn parse_end_of_quote(&mut self, window: &[u8]) -> Option<usize> {
+        let end = self.quote?;
+
+        if window.starts_with(end.as_bytes()) {
+            self.quote = None;
+            Some(end.len())
+        } else if window.starts_with(b"\\") {
+            // An escaped character can't end (or otherwise affect) the quote.
+            Some(2)
+        } else {
+            None
+        }
+    }
+
+    /// If `window` starts with the closing delimiter of the innermost open
+    /// multi-line comment, pops it off the stack.
+    pub fn parse_end_of_multi_line(&mut self, window: &[u8]) -> Option<usize> {
+        let end = *self.stack.last()?;
+
+        if window.starts_with(end.as_bytes()) {
+            self.stack.pop();
+            Some(end.len())
+        } else {
+            None
+        }
+    }
+
+    /// If `window` starts a quote or doc quote, opens it and records its
+    /// closing delimiter.
+    pub fn parse_quote(&mut self, window: &[u8]) -> Option<usize> {
+        for &(start, end) in self.doc_quotes {
+            if window.starts_with(start.as_bytes()) {
+                self.quote = Some(end);
+                self.quote_is_doc_quote = true;
+                return Some(start.len());
+            }
+        }
+
+        for &(start, end) in self.quotes {
+            if window.starts_with(start.as_bytes()) {
+                self.quote = Some(end);
+                self.quote_is_doc_quote = false;
+                return Some(start.len());
+            }
+        }
+
+        None
+    }
+
+    /// If `window` starts a multi-line comment, pushes its closing delimiter
+    /// onto `self.stack`.
+    pub fn parse_multi_line_comment(&mut self, window: &[u8]) -> Option<usize> {
+        if self.quote.is_some() {
+            return None;
+        }
+
+        for &(start, end) in self.multi_line_comments {
+            if window.starts_with(start.as_bytes()) {
+                self.stack.push(end);
+                return Some(start.len());
+            }
+        }
+
+        None
+    }
+
+    /// Returns `true` if `window` starts a line comment outside of any quote
+    /// or multi-line comment.
+    pub fn parse_line_comment(&self, window: &[u8]) -> bool {
+        self.quote.is_none()
+            && self.stack.is_empty()
+            && self
+                .line_comments
+                .iter()
+                .any(|comment| window.starts_with(comment.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embedded_region_opens_and_closes() {
+        let mut syntax = SyntaxCounter::new(LanguageType::Html);
+        assert_eq!(syntax.active().language, LanguageType::Html);
+
+        let skip = syntax.parse_embedded_open(b"<script>").unwrap();
+        assert_eq!(skip, "<script>".len());
+        assert_eq!(syntax.active().language, LanguageType::JavaScript);
+
+        assert!(syntax.parse_embedded_open(b"<script>").is_none());
+
+        let skip = syntax.parse_embedded_close(b"</script>").unwrap();
+        assert_eq!(skip, "</script>".len());
+        assert_eq!(syntax.active().language, LanguageType::Html);
+    }
+}
@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use grep_searcher::LineIter;
+
+use crate::{
+    config::Config,
+    language::{mixed, syntax::SyntaxCounter, LanguageType},
+    utils::ext::SliceExt,
+};
+
+/// The coarse kind of content a single physical line was classified as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A line of ordinary, countable code.
+    Code,
+    /// A line starting a (or continuing to be inside a) single-line comment.
+    LineComment,
+    /// A line inside (or starting/ending) a multi-line comment.
+    BlockComment,
+    /// A line inside (or starting/ending) an ordinary quoted string.
+    Quote,
+    /// A line inside (or starting/ending) a doc quote (e.g. Python's
+    /// `"""..."""`), regardless of whether `Config` treats it as a comment.
+    DocQuote,
+    /// A blank (whitespace-only) line.
+    Blank,
+}
+
+/// One physical line of source, classified by [`LanguageType::tokens`].
+#[derive(Clone, Copy, Debug)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub bytes: &'a [u8],
+    pub byte_range: Range<usize>,
+    /// The language the line was classified under: the host language `self`
+    /// was created with, or an embedded language if the line fell inside a
+    /// region such as an HTML `<script>` block.
+    pub language: LanguageType,
+    /// If [`Config::detailed_mixed_lines`] is set and this line mixed code
+    /// and comment content (e.g. `foo(); // note`), the
+    /// `(code_bytes, comment_bytes)` split either side of where the comment
+    /// starts. Always `None` otherwise.
+    pub mixed: Option<(usize, usize)>,
+}
+
+/// A streaming, allocation-light classifier over a file's physical lines.
+/// Carries the full [`SyntaxCounter`] state (quote stack,
+/// multi-line comment stack, embedded-region stack, the FORTRAN column
+/// rule), but reports a classification per line instead of driving a
+/// [`LanguageSummary`](crate::language::LanguageSummary).
+pub struct Tokens<'a> {
+    pub(crate) config: &'a Config,
+    pub(crate) syntax: SyntaxCounter,
+    pub(crate) lines: LineIter<'a>,
+    pub(crate) offset: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let raw_line = self.lines.next()?;
+        let byte_range = self.offset..self.offset + raw_line.len();
+        self.offset = byte_range.end;
+
+        let quote_open_at_line_start = self.syntax.active().quote;
+        let (kind, language) = classify_line(&mut self.syntax, raw_line);
+
+        let mixed = if self.config.detailed_mixed_lines {
+            mixed::classify(kind, self.syntax.active(), raw_line, quote_open_at_line_start)
+        } else {
+            None
+        };
+
+        Some(Token { kind, bytes: raw_line, byte_range, language, mixed })
+    }
+}
+
+/// Classifies `raw_line` using (and advancing) `syntax`'s state, including
+/// following it into or out of an embedded-language region. Never panics:
+/// anything this doesn't recognise as a delimiter is simply `Code`.
+pub(crate) fn classify_line<'a>(
+    syntax: &mut SyntaxCounter,
+    raw_line: &'a [u8],
+) -> (TokenKind, LanguageType) {
+    if raw_line.trim().is_empty() {
+        return (TokenKind::Blank, syntax.active().language);
+    }
+
+    let line = if syntax.active().is_fortran { raw_line } else { raw_line.trim() };
+    let had_multi_line = !syntax.active().stack.is_empty();
+    let start_language = syntax.active().language;
+
+    if let Some(kind) = classify_basic(syntax, line) {
+        return (kind, syntax.active().language);
+    }
+
+    let mut ended_with_comments = false;
+    let mut opened_embedded = false;
+    let mut skip = 0;
+    macro_rules! skip {
+        ($skip:expr) => {{
+            skip = $skip - 1;
+        }}
+    }
+
+    'window: for i in 0..line.len() {
+        if skip != 0 {
+            skip -= 1;
+            continue;
+        }
+
+        ended_with_comments = false;
+        let window = &line[i..];
+
+        let is_end_of_quote_or_multi_line = syntax.active_mut().parse_end_of_quote(window)
+            .or_else(|| syntax.active_mut().parse_end_of_multi_line(window));
+
+        if let Some(skip_amount) = is_end_of_quote_or_multi_line {
+            ended_with_comments = true;
+            skip!(skip_amount);
+            continue;
+        } else if syntax.active().quote.is_some() {
+            continue;
+        }
+
+        if let Some(skip_amount) = syntax.parse_embedded_close(window) {
+            skip!(skip_amount);
+            continue;
+        }
+
+        let is_quote_or_multi_line = syntax.active_mut().parse_quote(window)
+            .or_else(|| syntax.active_mut().parse_multi_line_comment(window));
+
+        if let Some(skip_amount) = is_quote_or_multi_line {
+            skip!(skip_amount);
+            continue;
+        }
+
+        if let Some(skip_amount) = syntax.parse_embedded_open(window) {
+            opened_embedded = true;
+            skip!(skip_amount);
+            continue;
+        }
+
+        if syntax.active_mut().parse_line_comment(window) {
+            ended_with_comments = true;
+            break 'window;
+        }
+    }
+
+    let scope = syntax.active();
+    let no_quote = scope.quote.is_none();
+    let is_doc_start = scope.doc_quotes.iter().any(|(s, _)| line.starts_with(s.as_bytes()));
+
+    let in_block_comment = (!scope.stack.is_empty() || ended_with_comments) && had_multi_line;
+    let starts_block_comment = no_quote
+        && scope.multi_line_comments.iter().any(|&(s, _)| line.starts_with(s.as_bytes()));
+    let starts_line_comment = no_quote
+        && scope.line_comments.iter().any(|s| line.starts_with(s.as_bytes()));
+    let in_doc_quote = scope.quote_is_doc_quote && (scope.quote.is_some() || is_doc_start);
+
+    let kind = if in_block_comment || starts_block_comment {
+        TokenKind::BlockComment
+    } else if starts_line_comment {
+        TokenKind::LineComment
+    } else if in_doc_quote {
+        TokenKind::DocQuote
+    } else if scope.quote.is_some() {
+        TokenKind::Quote
+    } else {
+        TokenKind::Code
+    };
+
+    // The line that opens an embedded region (e.g. the `<script>` tag) is
+    // markup in the host language, not the child it just pushed - mirrors
+    // how the host already keeps the line that *closes* a region, since
+    // `parse_embedded_close` pops the child before `scope` is read above.
+    let language = if opened_embedded { start_language } else { scope.language };
+
+    (kind, language)
+}
+
+/// Attempts to classify `line` as simply as possible, when there are no
+/// multi-line comments or quotes open and nothing on the line needs the
+/// full byte-by-byte scan. Returns `None` if a full scan is required.
+fn classify_basic(syntax: &SyntaxCounter, line: &[u8]) -> Option<TokenKind> {
+    let scope = syntax.active();
+
+    if scope.quote.is_some()
+        || !scope.stack.is_empty()
+        || syntax.important_syntax().any(|s| line.contains_slice(s.as_bytes()))
+    {
+        return None;
+    }
+
+    Some(if scope.line_comments.iter().any(|s| line.starts_with(s.as_bytes())) {
+        TokenKind::LineComment
+    } else {
+        TokenKind::Code
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_blanks_code_and_comments() {
+        let config = Config::new();
+        let text = b"fn main() {}\n\n// a comment\n";
+
+        let kinds: Vec<_> = LanguageType::Rust
+            .tokens(text, &config)
+            .map(|token| token.kind)
+            .collect();
+
+        assert_eq!(kinds, [TokenKind::Code, TokenKind::Blank, TokenKind::LineComment]);
+    }
+
+    #[test]
+    fn byte_ranges_point_back_into_the_source() {
+        let config = Config::new();
+        let text = b"one\ntwo\n";
+
+        let ranges: Vec<_> = LanguageType::Rust
+            .tokens(text, &config)
+            .map(|token| &text[token.byte_range])
+            .collect();
+
+        assert_eq!(ranges, [&b"one\n"[..], &b"two\n"[..]]);
+    }
+}
@@ -0,0 +1,20 @@
+//! Tokei counts lines of code and the like, quickly.
+//!
+//! This crate provides the core library used by the `tokei` binary. It
+//! exposes the [`LanguageType`](language::LanguageType) parser, the
+//! [`Config`](config::Config) used to tweak its behaviour, and the
+//! [`Stats`](stats::Stats) summary it produces.
+
+#[macro_use]
+extern crate log;
+
+pub mod config;
+pub mod language;
+pub mod stats;
+pub mod utils;
+
+pub use crate::{
+    config::Config,
+    language::{LanguageSummary, LanguageType},
+    stats::Stats,
+};
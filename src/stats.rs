@@ -0,0 +1,56 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use crate::language::LanguageType;
+
+/// Per-file (or per-language aggregate) line counts produced by
+/// [`LanguageType::parse`](crate::language::LanguageType::parse).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    /// The path of the file these statistics were generated from.
+    pub name: PathBuf,
+    /// The total number of blank lines.
+    pub blanks: usize,
+    /// The total number of lines of code.
+    pub code: usize,
+    /// The total number of comment lines.
+    pub comments: usize,
+    /// The total number of lines, equal to `blanks + code + comments`.
+    pub lines: usize,
+    /// The number of function/method definitions recognised.
+    pub functions: usize,
+    /// The number of type definitions (struct/class/enum/interface/...)
+    /// recognised.
+    pub types: usize,
+    /// The number of declarations recognised, i.e. `functions + types` plus
+    /// any other entity kinds future languages add. Nesting is not
+    /// considered: a method inside an `impl` or class counts as well as a
+    /// top-level item.
+    pub declarations: usize,
+    /// The number of lines excluded from every other count by a
+    /// `tokei:ignore-start`/`-end` directive (only ever non-zero when
+    /// [`Config::allow_directives`](crate::config::Config::allow_directives)
+    /// is set).
+    pub ignored_lines: usize,
+    /// Whether this file was detected as machine-generated, via an
+    /// `@generated` comment marker or a configured filename glob.
+    pub generated: bool,
+    /// The number of lines that mixed code and comment content on the same
+    /// physical line (e.g. `foo(); // note`), only ever non-zero when
+    /// [`Config::detailed_mixed_lines`](crate::config::Config::detailed_mixed_lines)
+    /// is set. These lines are still counted once above, in `code` or
+    /// `comments` by their ordinary classification.
+    pub mixed: usize,
+    /// Line tallies for languages embedded in this file (e.g. the JS inside
+    /// an HTML `<script>` block), keyed by the embedded language.
+    pub children: BTreeMap<LanguageType, Stats>,
+}
+
+impl Stats {
+    /// Creates a new `Stats` for the file at `name`, with every count at 0.
+    pub fn new(name: PathBuf) -> Self {
+        Stats {
+            name,
+            ..Self::default()
+        }
+    }
+}
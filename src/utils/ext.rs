@@ -0,0 +1,27 @@
+/// Small byte slice helpers used by the language parsers, where working with
+/// `&[u8]` directly is faster than paying for UTF-8 validation on every line.
+pub trait SliceExt {
+    /// Returns `true` if `self` contains `needle` anywhere within it.
+    fn contains_slice(&self, needle: &[u8]) -> bool;
+
+    /// Trims leading and trailing ASCII whitespace from `self`.
+    fn trim(&self) -> &[u8];
+}
+
+impl SliceExt for [u8] {
+    fn contains_slice(&self, needle: &[u8]) -> bool {
+        if needle.is_empty() {
+            return true;
+        }
+
+        self.windows(needle.len()).any(|window| window == needle)
+    }
+
+    fn trim(&self) -> &[u8] {
+        let is_space = |b: &u8| b.is_ascii_whitespace();
+        let start = self.iter().position(|b| !is_space(b)).unwrap_or(self.len());
+        let end = self.iter().rposition(|b| !is_space(b)).map_or(start, |i| i + 1);
+
+        &self[start..end]
+    }
+}
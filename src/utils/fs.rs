@@ -0,0 +1,9 @@
+use std::path::Path;
+
+/// Returns `true` if `path`'s file name starts with a `.`, as used to
+/// recognise dotfiles/dotdirs when walking a tree.
+pub fn is_dot_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map_or(false, |name| name.starts_with('.'))
+}
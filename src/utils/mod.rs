@@ -0,0 +1,2 @@
+pub mod ext;
+pub mod fs;